@@ -0,0 +1,329 @@
+use std::env;
+use std::io;
+use std::path::Path;
+
+use base64::Engine as _;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+
+/// A terminal image-display backend. Each adapter knows how to encode the
+/// pixels of an image into the escape-code payload its terminal protocol
+/// expects for a given `rect`, after the zoom/pan in `view` is applied.
+///
+/// `encode` does the (potentially slow) decode/encode work; it's what the
+/// scheduler runs off the UI thread, caching the resulting payload so the
+/// render path only ever has to write a string to stdout.
+pub trait Adapter: Send {
+    fn encode(&self, path: &Path, rect: Rect, view: View) -> io::Result<String>;
+}
+
+/// Zoom factor and pan offset applied to the decoded image before it's
+/// scaled down for the terminal, driven by the preview's zoom/pan mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    pub zoom: f32,
+    pub pan: (i32, i32),
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            zoom: 1.0,
+            pan: (0, 0),
+        }
+    }
+}
+
+/// Crop `img` down to the region selected by `view`'s zoom/pan before the
+/// caller resizes it to fit the terminal cell grid.
+fn apply_view(img: DynamicImage, view: View) -> DynamicImage {
+    if view.zoom <= 1.0 && view.pan == (0, 0) {
+        return img;
+    }
+
+    let zoom = view.zoom.max(1.0);
+    let (w, h) = (img.width() as f32, img.height() as f32);
+    let crop_w = (w / zoom).max(1.0);
+    let crop_h = (h / zoom).max(1.0);
+
+    let max_x = (w - crop_w).max(0.0);
+    let max_y = (h - crop_h).max(0.0);
+    let x = (max_x / 2.0 - view.pan.0 as f32).clamp(0.0, max_x) as u32;
+    let y = (max_y / 2.0 - view.pan.1 as f32).clamp(0.0, max_y) as u32;
+
+    img.crop_imm(x, y, crop_w as u32, crop_h as u32)
+}
+
+fn to_io_err(e: image::ImageError) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Plain x/y/width/height in terminal cells, decoupled from ratatui so this
+/// module has no UI-framework dependency.
+#[derive(Debug, Clone, Copy)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl From<ratatui::layout::Rect> for Rect {
+    fn from(r: ratatui::layout::Rect) -> Self {
+        Rect {
+            x: r.x,
+            y: r.y,
+            width: r.width,
+            height: r.height,
+        }
+    }
+}
+
+/// Which protocol to use for image display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Kitty,
+    Sixel,
+    Iterm2,
+    Ansi,
+}
+
+impl Kind {
+    /// Pick the best adapter for the current terminal, consulting `$TERM`
+    /// and `$TERM_PROGRAM` the way yazi probes for graphics support.
+    pub fn detect() -> Self {
+        if env::var("TERM_PROGRAM").map(|v| v == "iTerm.app").unwrap_or(false) {
+            return Kind::Iterm2;
+        }
+        if let Ok(term) = env::var("TERM") {
+            if term.contains("kitty") {
+                return Kind::Kitty;
+            }
+            if env::var("KITTY_WINDOW_ID").is_ok() {
+                return Kind::Kitty;
+            }
+            if term.contains("xterm") && env::var("VTE_VERSION").is_err() {
+                // Plain xterm with sixel support is common enough to probe,
+                // but most multiplexers advertise a different TERM.
+                return Kind::Sixel;
+            }
+        }
+        Kind::Ansi
+    }
+
+    /// Parse a user/CLI override (`--image-protocol kitty`, config file, …).
+    pub fn from_str_override(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "kitty" => Some(Kind::Kitty),
+            "sixel" => Some(Kind::Sixel),
+            "iterm2" | "iterm" => Some(Kind::Iterm2),
+            "ansi" | "half-block" | "halfblock" => Some(Kind::Ansi),
+            _ => None,
+        }
+    }
+
+    pub fn build(self) -> Box<dyn Adapter> {
+        match self {
+            Kind::Kitty => Box::new(KittyAdapter),
+            Kind::Sixel => Box::new(SixelAdapter),
+            Kind::Iterm2 => Box::new(Iterm2Adapter),
+            Kind::Ansi => Box::new(AnsiAdapter),
+        }
+    }
+}
+
+pub struct KittyAdapter;
+
+impl Adapter for KittyAdapter {
+    fn encode(&self, path: &Path, rect: Rect, view: View) -> io::Result<String> {
+        let img = apply_view(image::open(path).map_err(to_io_err)?, view);
+        let mut png = Vec::new();
+        img.write_to(&mut io::Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(to_io_err)?;
+        let b64 = base64::engine::general_purpose::STANDARD.encode(png);
+        let kitty_x = rect.x + 2;
+        let kitty_y = rect.y + 2;
+        let kitty_w = rect.width.saturating_sub(2);
+        let kitty_h = rect.height.saturating_sub(2);
+        Ok(format!(
+            "\x1b_Ga=d\x1b\\\x1b_Gf=100,a=T,C=1,q=2,X={},Y={},c={},r={};{}\x1b\\",
+            kitty_x, kitty_y, kitty_w, kitty_h, b64
+        ))
+    }
+}
+
+pub struct Iterm2Adapter;
+
+impl Adapter for Iterm2Adapter {
+    fn encode(&self, path: &Path, rect: Rect, view: View) -> io::Result<String> {
+        let img = apply_view(image::open(path).map_err(to_io_err)?, view);
+        let mut png = Vec::new();
+        img.write_to(&mut io::Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(to_io_err)?;
+        let b64 = base64::engine::general_purpose::STANDARD.encode(&png);
+        Ok(format!(
+            "\x1b]1337;File=inline=1;width={};height={};preserveAspectRatio=1:{}\x07",
+            rect.width, rect.height, b64
+        ))
+    }
+}
+
+pub struct SixelAdapter;
+
+const SIXEL_PALETTE_SIZE: usize = 256;
+
+impl Adapter for SixelAdapter {
+    fn encode(&self, path: &Path, rect: Rect, view: View) -> io::Result<String> {
+        let img = apply_view(image::open(path).map_err(to_io_err)?, view)
+            .resize(
+                rect.width as u32 * 8,
+                rect.height as u32 * 16,
+                FilterType::Triangle,
+            )
+            .to_rgb8();
+
+        let (w, h) = img.dimensions();
+        let (palette, indexed) = quantize(&img, SIXEL_PALETTE_SIZE);
+
+        let mut out = String::new();
+        out.push_str("\x1bPq");
+        for (i, (r, g, b)) in palette.iter().enumerate() {
+            out.push_str(&format!(
+                "#{};2;{};{};{}",
+                i,
+                r.saturating_mul(100) as u32 / 255,
+                g.saturating_mul(100) as u32 / 255,
+                b.saturating_mul(100) as u32 / 255
+            ));
+        }
+
+        for band_y in (0..h).step_by(6) {
+            for color_idx in 0..palette.len() {
+                let mut row = String::new();
+                let mut any = false;
+                for x in 0..w {
+                    let mut sixel_bits = 0u8;
+                    for dy in 0..6 {
+                        let y = band_y + dy;
+                        if y >= h {
+                            continue;
+                        }
+                        if indexed[(y * w + x) as usize] == color_idx as u8 {
+                            sixel_bits |= 1 << dy;
+                            any = true;
+                        }
+                    }
+                    row.push((0x3f + sixel_bits) as char);
+                }
+                if any {
+                    out.push('#');
+                    out.push_str(&color_idx.to_string());
+                    out.push_str(&row);
+                    out.push('$');
+                }
+            }
+            out.push('-');
+        }
+        out.push_str("\x1b\\");
+
+        Ok(format!("\x1b[{};{}H{}", rect.y + 1, rect.x + 1, out))
+    }
+}
+
+/// Naive uniform-cube quantizer: good enough to pick `max_colors` distinct
+/// palette entries without pulling in a dedicated quantization crate.
+fn quantize(
+    img: &image::RgbImage,
+    max_colors: usize,
+) -> (Vec<(u8, u8, u8)>, Vec<u8>) {
+    let side = (max_colors as f64).cbrt().floor().max(1.0) as u32;
+    // Round up so `255 / step` never exceeds `side - 1`, keeping the cube at
+    // `side³ <= max_colors` entries.
+    let step = (256 + side - 1) / side;
+
+    let mut palette = Vec::new();
+    let mut lookup = std::collections::HashMap::new();
+    let mut indexed = Vec::with_capacity(img.pixels().len());
+
+    for pixel in img.pixels() {
+        let [r, g, b] = pixel.0;
+        let key = (r / step as u8, g / step as u8, b / step as u8);
+        let idx = *lookup.entry(key).or_insert_with(|| {
+            let centered = (
+                key.0.saturating_mul(step as u8).saturating_add(step as u8 / 2),
+                key.1.saturating_mul(step as u8).saturating_add(step as u8 / 2),
+                key.2.saturating_mul(step as u8).saturating_add(step as u8 / 2),
+            );
+            palette.push(centered);
+            (palette.len() - 1) as u8
+        });
+        indexed.push(idx);
+    }
+
+    (palette, indexed)
+}
+
+pub struct AnsiAdapter;
+
+impl Adapter for AnsiAdapter {
+    fn encode(&self, path: &Path, rect: Rect, view: View) -> io::Result<String> {
+        let target_h = (rect.height as u32 * 2).max(1);
+        let img = apply_view(image::open(path).map_err(to_io_err)?, view)
+            .resize_exact(rect.width.max(1) as u32, target_h, FilterType::Triangle)
+            .to_rgb8();
+
+        let mut out = String::new();
+        for row in 0..rect.height {
+            out.push_str(&format!("\x1b[{};{}H", rect.y + row + 1, rect.x + 1));
+            for x in 0..rect.width as u32 {
+                let top = img.get_pixel(x, row as u32 * 2);
+                let bottom = img.get_pixel(x, (row as u32 * 2 + 1).min(target_h - 1));
+                out.push_str(&format!(
+                    "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                    top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]
+                ));
+            }
+            out.push_str("\x1b[0m");
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_view_is_a_no_op_at_default_zoom_and_pan() {
+        let img = DynamicImage::new_rgb8(20, 10);
+        let out = apply_view(img, View::default());
+        assert_eq!((out.width(), out.height()), (20, 10));
+    }
+
+    #[test]
+    fn apply_view_clamps_pan_so_the_crop_stays_in_bounds() {
+        let img = DynamicImage::new_rgb8(100, 100);
+        let view = View {
+            zoom: 4.0,
+            pan: (1_000, -1_000),
+        };
+        let out = apply_view(img, view);
+        // crop_w/crop_h = 100 / 4 = 25; a pan far beyond the image clamps to
+        // the edge instead of panicking or producing an out-of-bounds crop.
+        assert_eq!((out.width(), out.height()), (25, 25));
+    }
+
+    #[test]
+    fn quantize_keeps_indices_in_bounds_and_within_max_colors() {
+        let img = image::RgbImage::from_fn(16, 16, |x, y| {
+            image::Rgb([(x * 16) as u8, (y * 16) as u8, 128])
+        });
+
+        let (palette, indexed) = quantize(&img, 64);
+
+        assert!(palette.len() <= 64);
+        assert_eq!(indexed.len(), (16 * 16) as usize);
+        assert!(indexed.iter().all(|&idx| (idx as usize) < palette.len()));
+    }
+}
@@ -0,0 +1,391 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use image::{DynamicImage, ImageFormat, imageops::FilterType};
+
+use crate::events::Event;
+
+/// Target formats the conversion modal lets the user pick between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Jpeg,
+    WebP,
+    Bmp,
+    Gif,
+}
+
+impl Format {
+    pub const ALL: [Format; 5] = [
+        Format::Png,
+        Format::Jpeg,
+        Format::WebP,
+        Format::Bmp,
+        Format::Gif,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Format::Png => "PNG",
+            Format::Jpeg => "JPEG",
+            Format::WebP => "WebP",
+            Format::Bmp => "BMP",
+            Format::Gif => "GIF",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Jpeg => "jpg",
+            Format::WebP => "webp",
+            Format::Bmp => "bmp",
+            Format::Gif => "gif",
+        }
+    }
+
+    fn image_format(&self) -> ImageFormat {
+        match self {
+            Format::Png => ImageFormat::Png,
+            Format::Jpeg => ImageFormat::Jpeg,
+            Format::WebP => ImageFormat::WebP,
+            Format::Bmp => ImageFormat::Bmp,
+            Format::Gif => ImageFormat::Gif,
+        }
+    }
+}
+
+/// Which field of the conversion modal currently has keyboard focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Field {
+    #[default]
+    Format,
+    Width,
+    Height,
+    Quality,
+}
+
+impl Field {
+    fn next(self) -> Field {
+        match self {
+            Field::Format => Field::Width,
+            Field::Width => Field::Height,
+            Field::Height => Field::Quality,
+            Field::Quality => Field::Format,
+        }
+    }
+
+    fn prev(self) -> Field {
+        match self {
+            Field::Format => Field::Quality,
+            Field::Width => Field::Format,
+            Field::Height => Field::Width,
+            Field::Quality => Field::Height,
+        }
+    }
+}
+
+/// Conversion-modal state, held on `App` while the modal is open.
+#[derive(Debug, Default)]
+pub struct ConversionState {
+    pub active: bool,
+    pub source: Option<PathBuf>,
+    pub format_idx: usize,
+    pub width: String,
+    pub height: String,
+    pub quality: String,
+    pub focus: Field,
+    pub status: Option<String>,
+    /// Number of images this modal will act on when it was opened over a
+    /// multi-selection; 0 means it targets `source` alone.
+    pub batch_count: usize,
+}
+
+impl ConversionState {
+    pub fn open(&mut self, source: PathBuf) {
+        self.active = true;
+        self.source = Some(source);
+        self.format_idx = 0;
+        self.width.clear();
+        self.height.clear();
+        self.quality.clear();
+        self.focus = Field::Format;
+        self.status = None;
+        self.batch_count = 0;
+    }
+
+    /// Open the modal targeting `count` selected images instead of a single
+    /// `source`; the caller runs the batch via [`ConversionState::build_batch`].
+    pub fn open_batch(&mut self, count: usize) {
+        self.active = true;
+        self.source = None;
+        self.format_idx = 0;
+        self.width.clear();
+        self.height.clear();
+        self.quality.clear();
+        self.focus = Field::Format;
+        self.status = None;
+        self.batch_count = count;
+    }
+
+    pub fn close(&mut self) {
+        self.active = false;
+        self.source = None;
+        self.status = None;
+        self.batch_count = 0;
+    }
+
+    pub fn format(&self) -> Format {
+        Format::ALL[self.format_idx]
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = self.focus.next();
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus = self.focus.prev();
+    }
+
+    pub fn cycle_format(&mut self, forward: bool) {
+        let len = Format::ALL.len();
+        if forward {
+            self.format_idx = (self.format_idx + 1) % len;
+        } else {
+            self.format_idx = (self.format_idx + len - 1) % len;
+        }
+    }
+
+    /// Route a typed character to whichever field has focus.
+    pub fn push_char(&mut self, c: char) {
+        if !c.is_ascii_digit() {
+            return;
+        }
+        match self.focus {
+            Field::Width => self.width.push(c),
+            Field::Height => self.height.push(c),
+            Field::Quality => self.quality.push(c),
+            Field::Format => {}
+        }
+    }
+
+    pub fn pop_char(&mut self) {
+        match self.focus {
+            Field::Width => {
+                self.width.pop();
+            }
+            Field::Height => {
+                self.height.pop();
+            }
+            Field::Quality => {
+                self.quality.pop();
+            }
+            Field::Format => {}
+        }
+    }
+
+    /// Run the conversion with the modal's current settings, writing the
+    /// result next to the source file.
+    pub fn run(&mut self) -> std::io::Result<PathBuf> {
+        let source = self
+            .source
+            .clone()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no source image"))?;
+        let width = parse_dimension(&self.width);
+        let height = parse_dimension(&self.height);
+        let quality = parse_quality(&self.quality);
+        match convert_image(&source, self.format(), width, height, quality) {
+            Ok(out_path) => {
+                self.status = Some(format!("saved {}", out_path.display()));
+                Ok(out_path)
+            }
+            Err(e) => {
+                self.status = Some(format!("error: {e}"));
+                Err(e)
+            }
+        }
+    }
+
+    /// Turn `sources` into jobs using this modal's current format/resize/
+    /// quality settings, for the multi-select batch-conversion queue.
+    pub fn build_batch(&self, sources: Vec<PathBuf>) -> Vec<BatchJob> {
+        let width = parse_dimension(&self.width);
+        let height = parse_dimension(&self.height);
+        let quality = parse_quality(&self.quality);
+        let format = self.format();
+        sources
+            .into_iter()
+            .map(|source| BatchJob {
+                source,
+                format,
+                width,
+                height,
+                quality,
+            })
+            .collect()
+    }
+}
+
+/// Progress of a batch conversion, reported back through the event channel
+/// so the UI can show a "converting N/M" line without blocking, and (once
+/// `done == total`) how many of those jobs actually succeeded.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+    pub failed: usize,
+}
+
+pub struct BatchJob {
+    pub source: PathBuf,
+    pub format: Format,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub quality: Option<u8>,
+}
+
+/// Run `jobs` sequentially on a background thread, reporting progress after
+/// each one through `sender` so the main loop can update without blocking.
+/// Each job's `Result` is tallied into `Progress::failed` rather than
+/// discarded, so a run that fails every job (e.g. converting PNGs to PNG)
+/// doesn't get reported as a success.
+pub fn spawn_batch(jobs: Vec<BatchJob>, sender: mpsc::Sender<Event>) {
+    thread::spawn(move || {
+        let total = jobs.len();
+        let mut failed = 0;
+        for (i, job) in jobs.into_iter().enumerate() {
+            if convert_image(&job.source, job.format, job.width, job.height, job.quality).is_err()
+            {
+                failed += 1;
+            }
+            if sender
+                .send(Event::Progress(Progress {
+                    done: i + 1,
+                    total,
+                    failed,
+                }))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+fn parse_dimension(s: &str) -> Option<u32> {
+    let n: u32 = s.parse().ok()?;
+    if n == 0 { None } else { Some(n) }
+}
+
+fn parse_quality(s: &str) -> Option<u8> {
+    s.parse::<u8>().ok().map(|q| q.clamp(1, 100))
+}
+
+/// Decode `source`, optionally resize it, and write it out next to the
+/// original in `format`. Returns the path of the converted file.
+pub fn convert_image(
+    source: &Path,
+    format: Format,
+    width: Option<u32>,
+    height: Option<u32>,
+    quality: Option<u8>,
+) -> std::io::Result<PathBuf> {
+    let img = image::open(source).map_err(to_io_err)?;
+
+    let img = match (width, height) {
+        (Some(w), Some(h)) => img.resize(w, h, FilterType::Lanczos3),
+        (Some(w), None) => {
+            let h = w * img.height() / img.width().max(1);
+            img.resize(w, h, FilterType::Lanczos3)
+        }
+        (None, Some(h)) => {
+            let w = h * img.width() / img.height().max(1);
+            img.resize(w, h, FilterType::Lanczos3)
+        }
+        (None, None) => img,
+    };
+
+    let out_path = next_to(source, format.extension());
+
+    if out_path == source {
+        return Err(std::io::Error::other(format!(
+            "source and target are the same file ({}); pick a different format",
+            out_path.display()
+        )));
+    }
+
+    if format == Format::Jpeg {
+        save_jpeg(&img, &out_path, quality.unwrap_or(85))?;
+    } else {
+        img.save_with_format(&out_path, format.image_format())
+            .map_err(to_io_err)?;
+    }
+
+    Ok(out_path)
+}
+
+fn save_jpeg(img: &DynamicImage, out_path: &Path, quality: u8) -> std::io::Result<()> {
+    let file = std::fs::File::create(out_path)?;
+    let mut writer = std::io::BufWriter::new(file);
+    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut writer, quality);
+    img.write_with_encoder(encoder).map_err(to_io_err)
+}
+
+fn next_to(source: &Path, extension: &str) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "converted".into());
+    let dir = source.parent().unwrap_or_else(|| Path::new("."));
+    dir.join(format!("{stem}.{extension}"))
+}
+
+fn to_io_err(e: image::ImageError) -> std::io::Error {
+    std::io::Error::other(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dimension_rejects_zero_and_garbage() {
+        assert_eq!(parse_dimension("640"), Some(640));
+        assert_eq!(parse_dimension("0"), None);
+        assert_eq!(parse_dimension(""), None);
+        assert_eq!(parse_dimension("abc"), None);
+    }
+
+    #[test]
+    fn parse_quality_clamps_into_range() {
+        assert_eq!(parse_quality("85"), Some(85));
+        assert_eq!(parse_quality("0"), Some(1));
+        assert_eq!(parse_quality("255"), Some(100));
+        assert_eq!(parse_quality(""), None);
+    }
+
+    #[test]
+    fn next_to_keeps_stem_and_swaps_extension() {
+        let out = next_to(Path::new("/tmp/photos/cat.png"), "jpg");
+        assert_eq!(out, PathBuf::from("/tmp/photos/cat.jpg"));
+    }
+
+    #[test]
+    fn convert_image_refuses_to_overwrite_the_source() {
+        let dir = std::env::temp_dir().join(format!(
+            "tmages-convert-test-{:?}",
+            std::thread::current().id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("already.png");
+        image::DynamicImage::new_rgb8(2, 2)
+            .save_with_format(&source, ImageFormat::Png)
+            .unwrap();
+
+        let err = convert_image(&source, Format::Png, None, None, None).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
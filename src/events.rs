@@ -0,0 +1,79 @@
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+use crossterm::event::{self as ct_event, Event as CtEvent, KeyEvent, KeyEventKind};
+
+use crate::convert::Progress;
+use crate::watcher::DirWatcher;
+
+/// Everything the main loop can react to in one tick.
+#[derive(Debug)]
+pub enum Event {
+    Key(KeyEvent),
+    Refresh,
+    Progress(Progress),
+}
+
+/// Merges terminal input and filesystem-watch refreshes onto one channel so
+/// `App::run` can block on a single `recv` instead of juggling sources.
+pub struct EventHandler {
+    receiver: mpsc::Receiver<Event>,
+    sender: mpsc::Sender<Event>,
+    watcher: Option<DirWatcher>,
+}
+
+impl EventHandler {
+    pub fn new(watch_dir: &Path) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        let key_sender = sender.clone();
+        thread::spawn(move || loop {
+            match ct_event::read() {
+                Ok(CtEvent::Key(key)) if key.kind == KeyEventKind::Press => {
+                    if key_sender.send(Event::Key(key)).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        });
+
+        let watcher = DirWatcher::new(watch_dir, sender.clone()).ok();
+
+        Self {
+            receiver,
+            sender,
+            watcher,
+        }
+    }
+
+    pub fn next(&self) -> std::io::Result<Event> {
+        self.receiver
+            .recv()
+            .map_err(std::io::Error::other)
+    }
+
+    /// Clone of the sending half, for background workers (e.g. batch
+    /// conversion) that need to push events back into the main loop.
+    pub fn sender(&self) -> mpsc::Sender<Event> {
+        self.sender.clone()
+    }
+
+    /// Re-point the directory watcher after the user navigates elsewhere.
+    pub fn rewatch(&mut self, dir: &Path) {
+        match &mut self.watcher {
+            Some(watcher) => {
+                let _ = watcher.rewatch(dir);
+            }
+            None => self.watcher = DirWatcher::new(dir, self.sender.clone()).ok(),
+        }
+    }
+}
+
+impl std::fmt::Debug for EventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventHandler").finish_non_exhaustive()
+    }
+}
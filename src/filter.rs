@@ -0,0 +1,121 @@
+/// Score `candidate` against `query` as a case-insensitive subsequence
+/// match, the way a fuzzy-finder would: every query character must appear
+/// in order, with bonuses for runs of consecutive matches and for matches
+/// that land right after a `/`, `_`, `-` or a camelCase boundary. Returns
+/// `None` if `query` isn't a subsequence of `candidate` at all.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    // `str::to_lowercase` can change a string's char count (e.g. Turkish `İ`
+    // maps to two chars), which would desync a whole-string-lowercased `Vec`
+    // from `chars` and panic on index. Map char-by-char instead, taking only
+    // the first resulting char, so `lower` always has the same length as
+    // `chars` and the two can share an index.
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    let mut qi = 0;
+    let mut score: i64 = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (ci, &lc) in lower.iter().enumerate() {
+        if qi >= query.len() {
+            break;
+        }
+        if lc != query[qi] {
+            continue;
+        }
+
+        score += 1;
+        if last_match == Some(ci.wrapping_sub(1)) {
+            score += 5;
+        }
+        if ci == 0 {
+            score += 3;
+        } else {
+            let prev = chars[ci - 1];
+            let at_boundary = matches!(prev, '/' | '_' | '-')
+                || (prev.is_lowercase() && chars[ci].is_uppercase());
+            if at_boundary {
+                score += 3;
+            }
+        }
+
+        last_match = Some(ci);
+        qi += 1;
+    }
+
+    if qi == query.len() { Some(score) } else { None }
+}
+
+/// Indices of `files` that fuzzy-match `query`, sorted by descending score.
+/// An empty query matches everything in its original order.
+pub fn filter_and_sort(query: &str, files: &[String]) -> Vec<usize> {
+    if query.is_empty() {
+        return (0..files.len()).collect();
+    }
+
+    let mut scored: Vec<(usize, i64)> = files
+        .iter()
+        .enumerate()
+        .filter_map(|(i, name)| fuzzy_score(query, name).map(|score| (i, score)))
+        .collect();
+
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_matches_in_order_subsequence() {
+        assert!(fuzzy_score("", "anything.png").is_some());
+        assert!(fuzzy_score("cat", "vacation.png").is_some());
+        assert!(fuzzy_score("xyz", "vacation.png").is_none());
+    }
+
+    #[test]
+    fn fuzzy_score_rewards_boundaries_and_runs() {
+        let boundary = fuzzy_score("p", "vacation_photo.png").unwrap();
+        let mid_word = fuzzy_score("o", "vacation_photo.png").unwrap();
+        assert!(boundary > mid_word);
+    }
+
+    #[test]
+    fn fuzzy_score_only_rewards_a_genuine_lower_to_upper_transition() {
+        // The 'B' in "fooBar" follows a lowercase 'o': a real camelCase
+        // boundary. The 'A' in "FooBAR" follows an uppercase 'B': just a
+        // run of caps, not a boundary, so it shouldn't get the same bonus.
+        let camel_case = fuzzy_score("b", "fooBar.png").unwrap();
+        let upper_run = fuzzy_score("a", "FooBAR.png").unwrap();
+        assert!(camel_case > upper_run);
+    }
+
+    #[test]
+    fn fuzzy_score_does_not_panic_on_expanding_lowercase_chars() {
+        // `'İ'.to_lowercase()` yields two chars, which used to desync the
+        // per-char vectors and panic on index.
+        assert!(fuzzy_score("g", "İstanbul.png").is_some());
+        assert_eq!(fuzzy_score("z", "İstanbul.png"), None);
+    }
+
+    #[test]
+    fn filter_and_sort_ranks_best_match_first() {
+        let files = vec![
+            "c_a_t.png".to_string(),
+            "cat.png".to_string(),
+            "dog.png".to_string(),
+        ];
+        let order = filter_and_sort("cat", &files);
+        assert_eq!(order.first().copied(), Some(1));
+        assert!(!order.contains(&2));
+    }
+}
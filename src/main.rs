@@ -1,24 +1,30 @@
-use base64::Engine as _;
-use std::{
-    fs,
-    io::{self, Write},
-    path::PathBuf,
-};
+mod adapter;
+mod convert;
+mod events;
+mod filter;
+mod scheduler;
+mod watcher;
 
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
-    terminal,
-};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use events::{Event, EventHandler};
 use ratatui::{
-    DefaultTerminal, Frame, Terminal,
+    DefaultTerminal, Frame,
     buffer::Buffer,
     layout::Rect,
     style::{Modifier, Style, Stylize},
     symbols::border,
     text::{Line, Text},
-    widgets::{Block, Paragraph, Widget},
+    widgets::{Block, Clear, Paragraph, Widget},
 };
 
+use convert::{ConversionState, Field, Progress};
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
 fn main() -> std::io::Result<()> {
     let mut terminal = ratatui::init();
     let app_result = App::new()?.run(&mut terminal);
@@ -33,21 +39,64 @@ pub struct App {
     selected: usize,
     scroll: usize,
     exit: bool,
+    conversion: ConversionState,
+    adapter_kind: adapter::Kind,
+    scheduler: scheduler::Scheduler,
+    events: EventHandler,
+    selected_set: HashSet<usize>,
+    progress: Option<Progress>,
+    search_active: bool,
+    search_query: String,
+    filtered_indices: Vec<usize>,
+    preview_focus: bool,
+    zoom: f32,
+    pan: (i32, i32),
 }
 
 impl App {
     pub fn new() -> std::io::Result<Self> {
         let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
         let files = Self::read_dir(&home)?;
+        let events = EventHandler::new(&home);
         Ok(Self {
             current_dir: home,
             files,
             selected: 0,
             scroll: 0,
             exit: false,
+            conversion: ConversionState::default(),
+            adapter_kind: Self::detect_adapter_kind(),
+            scheduler: scheduler::Scheduler::new(),
+            events,
+            selected_set: HashSet::new(),
+            progress: None,
+            search_active: false,
+            search_query: String::new(),
+            filtered_indices: Vec::new(),
+            preview_focus: false,
+            zoom: 1.0,
+            pan: (0, 0),
         })
     }
 
+    /// Resolve the display adapter: a `--image-protocol`/`TMAGES_IMAGE_PROTOCOL`
+    /// override wins, otherwise fall back to terminal auto-detection.
+    fn detect_adapter_kind() -> adapter::Kind {
+        let cli_override = std::env::args()
+            .collect::<Vec<_>>()
+            .windows(2)
+            .find(|w| w[0] == "--image-protocol")
+            .and_then(|w| adapter::Kind::from_str_override(&w[1]));
+
+        let env_override = std::env::var("TMAGES_IMAGE_PROTOCOL")
+            .ok()
+            .and_then(|v| adapter::Kind::from_str_override(&v));
+
+        cli_override
+            .or(env_override)
+            .unwrap_or_else(adapter::Kind::detect)
+    }
+
     pub fn read_dir(path: &PathBuf) -> std::io::Result<Vec<String>> {
         let mut entries = vec![];
         entries.push("..".into());
@@ -73,53 +122,312 @@ impl App {
     }
 
     pub fn handle_event(&mut self) -> std::io::Result<()> {
-        match event::read()? {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.handle_key_event(key_event)
+        match self.events.next()? {
+            Event::Key(key_event) => self.handle_key_event(key_event),
+            Event::Refresh => self.refresh_preserving_selection(),
+            Event::Progress(progress) => {
+                self.progress = Some(progress);
             }
-            _ => {}
-        };
+        }
         Ok(())
     }
 
+    /// Re-read `current_dir` (triggered by the directory watcher) while
+    /// keeping the same file selected by name, if it still exists.
+    fn refresh_preserving_selection(&mut self) {
+        let selected_name = self.files.get(self.selected).cloned();
+        self.selected_set.clear();
+        if let Ok(new_files) = Self::read_dir(&self.current_dir) {
+            self.files = new_files;
+            self.selected = selected_name
+                .and_then(|name| self.files.iter().position(|f| *f == name))
+                .unwrap_or(0)
+                .min(self.files.len().saturating_sub(1));
+            self.reset_view();
+            if self.selected < self.scroll {
+                self.scroll = self.selected;
+            }
+            if self.search_active {
+                self.update_filter();
+            }
+        }
+    }
+
     fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if self.conversion.active {
+            self.handle_conversion_key(key_event);
+            return;
+        }
+
+        if self.preview_focus {
+            self.handle_preview_key(key_event);
+            return;
+        }
+
+        if self.search_active {
+            self.handle_search_key(key_event);
+            return;
+        }
+
         match key_event.code {
             KeyCode::Char('q') => self.exit(),
 
-            KeyCode::Up => {
-                if self.selected > 0 {
-                    self.selected -= 1;
-                    if self.selected < self.scroll as usize {
-                        self.scroll = self.selected;
-                    }
-                }
+            KeyCode::Char('c') => self.open_conversion_modal(),
+
+            KeyCode::Char(' ') => self.toggle_select(),
+            KeyCode::Char('v') => self.invert_selection(),
+            KeyCode::Char('u') => self.clear_selection(),
+
+            KeyCode::Char('/') => self.enter_search(),
+
+            KeyCode::Tab => self.preview_focus = true,
+
+            KeyCode::Up => self.move_within_order(-1),
+            KeyCode::Down => self.move_within_order(1),
+
+            KeyCode::Enter => self.activate_selected(),
+
+            _ => {}
+        }
+    }
+
+    /// Zoom/pan the preview while it has focus (entered via `Tab`); `+`/`-`
+    /// zoom in and out around the centre, arrow keys shift the pan offset.
+    /// `Tab`/`Esc` hands focus back to the file list.
+    fn handle_preview_key(&mut self, key_event: KeyEvent) {
+        const ZOOM_STEP: f32 = 0.25;
+        const MAX_ZOOM: f32 = 8.0;
+        const PAN_STEP: i32 = 10;
+
+        match key_event.code {
+            KeyCode::Tab | KeyCode::Esc => self.preview_focus = false,
+
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.zoom = (self.zoom + ZOOM_STEP).min(MAX_ZOOM);
+            }
+            KeyCode::Char('-') => {
+                self.zoom = (self.zoom - ZOOM_STEP).max(1.0);
             }
 
-            KeyCode::Down => {
-                if self.selected + 1 < self.files.len() {
-                    self.selected += 1;
-                    if self.selected >= self.scroll {
-                        self.scroll = self.selected;
-                    }
+            KeyCode::Up => self.pan.1 -= PAN_STEP,
+            KeyCode::Down => self.pan.1 += PAN_STEP,
+            KeyCode::Left => self.pan.0 -= PAN_STEP,
+            KeyCode::Right => self.pan.0 += PAN_STEP,
+
+            _ => {}
+        }
+    }
+
+    /// Reset zoom/pan back to defaults, called whenever `selected` changes
+    /// so a new image never inherits the previous one's framing.
+    fn reset_view(&mut self) {
+        self.zoom = 1.0;
+        self.pan = (0, 0);
+    }
+
+    /// Indices into `files` that should currently be shown/navigated, in
+    /// display order: every file normally, or only the fuzzy matches while
+    /// a search is active.
+    fn display_order(&self) -> Vec<usize> {
+        if self.search_active {
+            self.filtered_indices.clone()
+        } else {
+            (0..self.files.len()).collect()
+        }
+    }
+
+    /// Move the selection up/down (`delta` of -1/1) within the current
+    /// display order, whether that's the full list or a search's matches.
+    fn move_within_order(&mut self, delta: i32) {
+        let order = self.display_order();
+        let Some(pos) = order.iter().position(|&i| i == self.selected) else {
+            return;
+        };
+
+        if delta < 0 && pos > 0 {
+            let new_pos = pos - 1;
+            self.selected = order[new_pos];
+            self.reset_view();
+            if new_pos < self.scroll {
+                self.scroll = new_pos;
+            }
+        } else if delta > 0 && pos + 1 < order.len() {
+            let new_pos = pos + 1;
+            self.selected = order[new_pos];
+            self.reset_view();
+            if new_pos >= self.scroll {
+                self.scroll = new_pos;
+            }
+        }
+    }
+
+    /// `Enter`'s action: descend into a selected directory or go up via
+    /// `..`, re-reading the listing and re-pointing the directory watcher.
+    fn activate_selected(&mut self) {
+        if let Some(name) = self.files.get(self.selected).cloned() {
+            if name == ".." {
+                if let Some(parent) = self.current_dir.parent() {
+                    self.current_dir = parent.to_path_buf();
                 }
+            } else {
+                let candidate = self.current_dir.join(name.trim_end_matches('/'));
+                if candidate.is_dir() {
+                    self.current_dir = candidate;
+                }
+            }
+            if let Ok(new_files) = Self::read_dir(&self.current_dir) {
+                self.files = new_files;
+                self.selected = 0;
+                self.scroll = 0;
+                self.selected_set.clear();
+                self.reset_view();
+            }
+            self.events.rewatch(&self.current_dir);
+        }
+    }
+
+    fn enter_search(&mut self) {
+        self.search_active = true;
+        self.search_query.clear();
+        self.update_filter();
+    }
+
+    fn exit_search(&mut self) {
+        self.search_active = false;
+        self.search_query.clear();
+        self.filtered_indices.clear();
+    }
+
+    fn update_filter(&mut self) {
+        self.filtered_indices = filter::filter_and_sort(&self.search_query, &self.files);
+        if !self.filtered_indices.contains(&self.selected) {
+            self.selected = self.filtered_indices.first().copied().unwrap_or(0);
+            self.scroll = 0;
+            self.reset_view();
+        }
+    }
+
+    fn handle_search_key(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc => self.exit_search(),
+
+            KeyCode::Enter => {
+                self.activate_selected();
+                self.exit_search();
+            }
+
+            KeyCode::Backspace => {
+                self.search_query.pop();
+                self.update_filter();
+            }
+
+            KeyCode::Char(c) => {
+                self.search_query.push(c);
+                self.update_filter();
+            }
+
+            KeyCode::Up => self.move_within_order(-1),
+            KeyCode::Down => self.move_within_order(1),
+
+            _ => {}
+        }
+    }
+
+    fn exit(&mut self) {
+        self.exit = true;
+    }
+
+    fn selected_path(&self) -> PathBuf {
+        self.current_dir
+            .join(self.files[self.selected].trim_end_matches('/'))
+    }
+
+    fn is_image(path: &std::path::Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false)
+    }
+
+    fn open_conversion_modal(&mut self) {
+        if !self.selected_set.is_empty() {
+            self.conversion.open_batch(self.selected_set.len());
+            return;
+        }
+        let path = self.selected_path();
+        if path.is_file() && Self::is_image(&path) {
+            self.conversion.open(path);
+        }
+    }
+
+    fn toggle_select(&mut self) {
+        if !self.selected_set.remove(&self.selected) {
+            self.selected_set.insert(self.selected);
+        }
+    }
+
+    fn invert_selection(&mut self) {
+        self.selected_set = (0..self.files.len())
+            .filter(|i| self.files[*i] != "..")
+            .filter(|i| !self.selected_set.contains(i))
+            .collect();
+    }
+
+    fn clear_selection(&mut self) {
+        self.selected_set.clear();
+    }
+
+    /// Queue every selected image for conversion on a background worker,
+    /// reporting progress back through the event channel.
+    fn start_batch_conversion(&mut self) {
+        let sources: Vec<PathBuf> = self
+            .selected_set
+            .iter()
+            .filter_map(|&i| self.files.get(i))
+            .filter(|name| *name != "..")
+            .map(|name| self.current_dir.join(name.trim_end_matches('/')))
+            .filter(|path| path.is_file() && Self::is_image(path))
+            .collect();
+
+        if sources.is_empty() {
+            return;
+        }
+
+        let jobs = self.conversion.build_batch(sources);
+        self.progress = Some(Progress {
+            done: 0,
+            total: jobs.len(),
+            failed: 0,
+        });
+        self.selected_set.clear();
+        convert::spawn_batch(jobs, self.events.sender());
+    }
+
+    fn handle_conversion_key(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc => self.conversion.close(),
+
+            KeyCode::Tab => self.conversion.focus_next(),
+            KeyCode::BackTab => self.conversion.focus_prev(),
+
+            KeyCode::Left if self.conversion.focus == Field::Format => {
+                self.conversion.cycle_format(false)
+            }
+            KeyCode::Right if self.conversion.focus == Field::Format => {
+                self.conversion.cycle_format(true)
             }
 
+            KeyCode::Char(c) => self.conversion.push_char(c),
+            KeyCode::Backspace => self.conversion.pop_char(),
+
             KeyCode::Enter => {
-                if let Some(name) = self.files.get(self.selected).cloned() {
-                    if name == ".." {
-                        if let Some(parent) = self.current_dir.parent() {
-                            self.current_dir = parent.to_path_buf();
-                        }
-                    } else {
-                        let candidate = self.current_dir.join(&name.trim_end_matches('/'));
-                        if candidate.is_dir() {
-                            self.current_dir = candidate;
-                        }
-                    }
+                if self.conversion.batch_count > 0 {
+                    self.start_batch_conversion();
+                    self.conversion.close();
+                } else {
+                    let _ = self.conversion.run();
                     if let Ok(new_files) = Self::read_dir(&self.current_dir) {
                         self.files = new_files;
-                        self.selected = 0;
-                        self.scroll = 0;
                     }
                 }
             }
@@ -128,10 +436,6 @@ impl App {
         }
     }
 
-    fn exit(&mut self) {
-        self.exit = true;
-    }
-
     pub fn draw(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.area());
     }
@@ -140,14 +444,55 @@ impl App {
 impl Widget for &App {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let title = Line::from("< Tmages - image converter TUI >".green().bold());
-        let instructions = Line::from(vec![
-            " Up/Down ".into(),
-            "<↑/↓>".blue().bold(),
-            " Enter ".into(),
-            "<↵>".blue().bold(),
-            " Quit ".into(),
-            "<Q>".red().bold(),
-        ]);
+        let instructions = if self.search_active {
+            Line::from(format!(" / {}", self.search_query).yellow().bold())
+        } else if let Some(progress) = self.progress {
+            if progress.done < progress.total {
+                Line::from(
+                    format!(" Converting {}/{} ", progress.done, progress.total)
+                        .yellow()
+                        .bold(),
+                )
+            } else if progress.failed > 0 {
+                Line::from(
+                    format!(
+                        " Converted {}, failed {} ",
+                        progress.done - progress.failed,
+                        progress.failed
+                    )
+                    .yellow()
+                    .bold(),
+                )
+            } else {
+                Line::from(format!(" Converted {} ", progress.done).yellow().bold())
+            }
+        } else if self.preview_focus {
+            Line::from(vec![
+                " Zoom ".into(),
+                "<+/->".blue().bold(),
+                " Pan ".into(),
+                "<Arrows>".blue().bold(),
+                " Back to list ".into(),
+                "<Tab>".blue().bold(),
+            ])
+        } else {
+            Line::from(vec![
+                " Up/Down ".into(),
+                "<↑/↓>".blue().bold(),
+                " Enter ".into(),
+                "<↵>".blue().bold(),
+                " Select ".into(),
+                "<Space>".blue().bold(),
+                " Search ".into(),
+                "</>".blue().bold(),
+                " Convert ".into(),
+                "<C>".blue().bold(),
+                " Zoom ".into(),
+                "<Tab>".blue().bold(),
+                " Quit ".into(),
+                "<Q>".red().bold(),
+            ])
+        };
 
         let outer = Block::bordered()
             .title(title.centered())
@@ -174,15 +519,27 @@ impl Widget for &App {
         if selected_path.is_file() {
             if let Some(ext) = selected_path.extension().and_then(|e| e.to_str()) {
                 let ext = ext.to_lowercase();
-                if ["png", "jpg", "jpeg", "gif", "bmp", "webp"].contains(&ext.as_str()) {
-                    // Display image in the preview area
-                    let _ = kitty_display_image(
-                        selected_path.to_str().unwrap(),
-                        preview_rect.x,
-                        preview_rect.y,
-                        preview_rect.width,
-                        preview_rect.height,
-                    );
+                if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+                    let view = adapter::View {
+                        zoom: self.zoom,
+                        pan: self.pan,
+                    };
+                    match self.scheduler.request(
+                        &selected_path,
+                        preview_rect.into(),
+                        self.adapter_kind,
+                        view,
+                    ) {
+                        Some(payload) => {
+                            print!("{payload}");
+                            let _ = std::io::Write::flush(&mut std::io::stdout());
+                        }
+                        None => {
+                            Paragraph::new("loading…")
+                                .block(Block::default())
+                                .render(preview_rect, buf);
+                        }
+                    }
                 }
             }
         }
@@ -194,33 +551,42 @@ impl Widget for &App {
 
         let max_visible = chunks[0].height.saturating_sub(2) as usize;
 
-        let total = self.files.len();
-        let mut scroll = self.scroll;
+        let order = self.display_order();
+        let total = order.len();
 
-        if self.selected >= scroll + max_visible {
-            scroll = self.selected + 1 - max_visible;
+        let pos = order.iter().position(|&i| i == self.selected).unwrap_or(0);
+        let mut scroll = self.scroll.min(total);
+        if pos >= scroll + max_visible {
+            scroll = pos + 1 - max_visible;
         }
-        if self.selected < scroll {
-            scroll = self.selected;
+        if pos < scroll {
+            scroll = pos;
         }
 
-        let start = self.scroll.min(total);
+        let start = scroll.min(total);
         let end = (start + max_visible).min(total);
 
-        let file_lines: Vec<Line> = self.files[start as usize..end as usize]
+        let file_lines: Vec<Line> = order[start..end]
             .iter()
-            .enumerate()
-            .map(|(i, name)| {
-                let absolute_index = start + i;
+            .map(|&absolute_index| {
+                let name = &self.files[absolute_index];
+                let marked = self.selected_set.contains(&absolute_index);
+                let label = if marked {
+                    format!("✓ {name}")
+                } else {
+                    format!("  {name}")
+                };
                 if absolute_index == self.selected {
-                    Line::from(name.clone()).style(
+                    Line::from(label).style(
                         Style::default()
                             .bg(ratatui::style::Color::Blue)
                             .fg(ratatui::style::Color::White)
                             .add_modifier(Modifier::BOLD),
                     )
+                } else if marked {
+                    Line::from(label).style(Style::default().fg(ratatui::style::Color::Green))
                 } else {
-                    Line::from(name.clone())
+                    Line::from(label)
                 }
             })
             .collect();
@@ -236,20 +602,60 @@ impl Widget for &App {
             .title(" Preview ".blue().bold().into_right_aligned_line())
             .border_set(border::PLAIN)
             .render(preview_rect, buf);
+
+        if self.conversion.active {
+            render_conversion_modal(&self.conversion, preview_rect, buf);
+        }
     }
 }
 
-fn kitty_display_image(path: &str, x: u16, y: u16, w: u16, h: u16) -> io::Result<()> {
-    let data = fs::read(path)?;
-    let b64 = base64::engine::general_purpose::STANDARD.encode(data);
-    print!("\x1b_Ga=d\x1b\\");
-    let kitty_x = x + 2; // Add 1 for 1-indexing + 1 for border
-    let kitty_y = y + 2; // Add 1 for 1-indexing + 1 for border
-    let kitty_w = w.saturating_sub(2); // Subtract border width
-    let kitty_h = h.saturating_sub(2); // Subtract border height
-    print!(
-        "\x1b_Gf=100,a=T,C=1,q=2,X={},Y={},c={},r={};{}\x1b\\",
-        kitty_x, kitty_y, kitty_w, kitty_h, b64
-    );
-    io::stdout().flush()
+fn render_conversion_modal(conversion: &ConversionState, area: Rect, buf: &mut Buffer) {
+    let modal_rect = Rect {
+        x: area.x + area.width / 6,
+        y: area.y + area.height / 4,
+        width: (area.width * 2 / 3).max(20),
+        height: (area.height / 2).max(8),
+    };
+
+    Clear.render(modal_rect, buf);
+
+    let focus_marker = |field: Field| if conversion.focus == field { ">" } else { " " };
+
+    let lines = vec![
+        Line::from(format!(
+            "{} Format: < {} >",
+            focus_marker(Field::Format),
+            conversion.format().label()
+        )),
+        Line::from(format!(
+            "{} Width:  {}",
+            focus_marker(Field::Width),
+            conversion.width
+        )),
+        Line::from(format!(
+            "{} Height: {}",
+            focus_marker(Field::Height),
+            conversion.height
+        )),
+        Line::from(format!(
+            "{} Quality (JPEG): {}",
+            focus_marker(Field::Quality),
+            conversion.quality
+        )),
+        Line::from(""),
+        if conversion.batch_count > 0 {
+            Line::from(format!("Converting {} selected images", conversion.batch_count))
+        } else {
+            Line::from(conversion.status.clone().unwrap_or_default())
+        },
+        Line::from("Tab/Shift-Tab move, ←/→ change format, Enter convert, Esc cancel"),
+    ];
+
+    Paragraph::new(Text::from(lines))
+        .block(
+            Block::bordered()
+                .title(" Convert ".yellow().bold().into_centered_line())
+                .border_set(border::PLAIN),
+        )
+        .render(modal_rect, buf);
 }
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::SystemTime;
+
+use indexmap::IndexMap;
+
+use crate::adapter;
+
+/// Max number of pre-encoded preview payloads kept around. Entries beyond
+/// this are evicted oldest-first, same as yazi's preview cache.
+const CACHE_CAPACITY: usize = 64;
+
+struct Job {
+    key: String,
+    path: PathBuf,
+    rect: adapter::Rect,
+    kind: adapter::Kind,
+    view: adapter::View,
+}
+
+/// Decodes and pre-encodes preview payloads off the UI thread, and caches
+/// the result keyed on `path + mtime + rect` so a steady selection never
+/// re-pays the decode cost on every frame.
+#[derive(Debug)]
+pub struct Scheduler {
+    sender: mpsc::Sender<Job>,
+    cache: Arc<Mutex<IndexMap<String, String>>>,
+    pending: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let cache = Arc::new(Mutex::new(IndexMap::new()));
+        let pending = Arc::new(Mutex::new(HashSet::new()));
+
+        let worker_cache = Arc::clone(&cache);
+        let worker_pending = Arc::clone(&pending);
+        thread::spawn(move || {
+            for job in receiver {
+                if let Ok(payload) = job.kind.build().encode(&job.path, job.rect, job.view) {
+                    let mut cache = worker_cache.lock().unwrap();
+                    cache.insert(job.key.clone(), payload);
+                    while cache.len() > CACHE_CAPACITY {
+                        cache.shift_remove_index(0);
+                    }
+                }
+                worker_pending.lock().unwrap().remove(&job.key);
+            }
+        });
+
+        Self {
+            sender,
+            cache,
+            pending,
+        }
+    }
+
+    /// Look up the cached payload for `path` rendered at `rect` with `kind`
+    /// and `view` (zoom/pan). On a miss, enqueues a background job (unless
+    /// one is already in flight) and returns `None` so the caller can show
+    /// a placeholder.
+    pub fn request(
+        &self,
+        path: &Path,
+        rect: adapter::Rect,
+        kind: adapter::Kind,
+        view: adapter::View,
+    ) -> Option<String> {
+        let key = cache_key(path, rect, kind, view);
+
+        {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some(payload) = cache.shift_remove(&key) {
+                cache.insert(key, payload.clone());
+                return Some(payload);
+            }
+        }
+
+        let mut pending = self.pending.lock().unwrap();
+        if pending.insert(key.clone()) {
+            let _ = self.sender.send(Job {
+                key,
+                path: path.to_path_buf(),
+                rect,
+                kind,
+                view,
+            });
+        }
+        None
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn cache_key(path: &Path, rect: adapter::Rect, kind: adapter::Kind, view: adapter::View) -> String {
+    let mtime = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+
+    let raw = format!(
+        "{}|{}|{:?}|{}x{}+{}+{}|{}@{},{}",
+        path.display(),
+        mtime,
+        kind,
+        rect.width,
+        rect.height,
+        rect.x,
+        rect.y,
+        view.zoom,
+        view.pan.0,
+        view.pan.1
+    );
+    format!("{:x}", md5::compute(raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect() -> adapter::Rect {
+        adapter::Rect {
+            x: 0,
+            y: 0,
+            width: 40,
+            height: 20,
+        }
+    }
+
+    #[test]
+    fn cache_key_is_stable_for_the_same_inputs() {
+        let path = std::env::temp_dir().join("tmages-cache-key-test.png");
+        std::fs::write(&path, b"not really a png").unwrap();
+
+        let a = cache_key(&path, rect(), adapter::Kind::Ansi, adapter::View::default());
+        let b = cache_key(&path, rect(), adapter::Kind::Ansi, adapter::View::default());
+        assert_eq!(a, b);
+
+        let different_view = adapter::View {
+            zoom: 2.0,
+            pan: (0, 0),
+        };
+        let c = cache_key(&path, rect(), adapter::Kind::Ansi, different_view);
+        assert_ne!(a, c);
+
+        let different_kind = cache_key(&path, rect(), adapter::Kind::Kitty, adapter::View::default());
+        assert_ne!(a, different_kind);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
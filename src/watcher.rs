@@ -0,0 +1,66 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::events::Event;
+
+/// Debounce window: a burst of filesystem events arriving within this
+/// interval collapses into a single refresh, the way yazi/hunter debounce
+/// their directory watchers.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watches a single directory and pushes a debounced [`Event::Refresh`]
+/// into the app's event channel whenever its contents change.
+pub struct DirWatcher {
+    watcher: RecommendedWatcher,
+    watched: PathBuf,
+}
+
+impl DirWatcher {
+    pub fn new(path: &Path, app_sender: Sender<Event>) -> notify::Result<Self> {
+        let (raw_tx, raw_rx) = std::sync::mpsc::channel::<notify::Result<notify::Event>>();
+
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = raw_tx.send(res);
+        })?;
+        watcher.watch(path, RecursiveMode::NonRecursive)?;
+
+        thread::spawn(move || {
+            loop {
+                if raw_rx.recv().is_err() {
+                    break;
+                }
+                // Drain the burst: keep resetting the window until events
+                // stop arriving, then fire a single refresh.
+                while raw_rx.recv_timeout(DEBOUNCE).is_ok() {}
+                if app_sender.send(Event::Refresh).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            watcher,
+            watched: path.to_path_buf(),
+        })
+    }
+
+    /// Point the watcher at a new directory, e.g. after the user navigates.
+    pub fn rewatch(&mut self, path: &Path) -> notify::Result<()> {
+        let _ = self.watcher.unwatch(&self.watched);
+        self.watcher.watch(path, RecursiveMode::NonRecursive)?;
+        self.watched = path.to_path_buf();
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for DirWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirWatcher")
+            .field("watched", &self.watched)
+            .finish()
+    }
+}